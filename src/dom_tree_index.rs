@@ -0,0 +1,106 @@
+use crate::{GraphRef, Point};
+use std::collections::BTreeMap;
+
+/// A preprocessed dominator tree that answers `dominates` queries with
+/// two map lookups and a comparison -- O(log n) in the number of
+/// indexed nodes, independent of tree depth -- instead of the O(depth)
+/// walk that `GraphRef::dominates` does on demand.
+///
+/// The technique is a standard Euler-tour / interval labeling: do a
+/// single DFS over the dominator tree (starting at `P::entry()`),
+/// assigning each node an entry timestamp `tin` and an exit timestamp
+/// `tout`. A child's `[tin, tout]` interval is always nested inside
+/// its parent's, so `A` dominates `B` iff:
+///
+/// ```text
+/// tin[A] <= tin[B] && tout[B] <= tout[A]
+/// ```
+#[derive(Clone, Debug)]
+pub struct DomTreeIndex<P: Point> {
+    tin: BTreeMap<P, u32>,
+    tout: BTreeMap<P, u32>,
+}
+
+impl<P: Point> DomTreeIndex<P> {
+    /// Builds the index by walking the dominator tree of `graph`. The
+    /// caller must supply every node reachable from the entry --
+    /// nodes that are never passed in simply will not be indexed.
+    pub fn new(graph: impl GraphRef<P>, nodes: impl IntoIterator<Item = P>) -> Self {
+        let entry = P::entry();
+        let mut children: BTreeMap<P, Vec<P>> = BTreeMap::new();
+        for node in nodes {
+            if node == entry {
+                continue;
+            }
+
+            let idom = graph
+                .immediate_dominator(node)
+                .expect("node is not reachable in the dominator tree");
+            children.entry(idom).or_insert_with(Vec::new).push(node);
+        }
+
+        let mut index = DomTreeIndex {
+            tin: BTreeMap::new(),
+            tout: BTreeMap::new(),
+        };
+        let mut timer = 0;
+        index.dfs(entry, &children, &mut timer);
+        index
+    }
+
+    fn dfs(&mut self, node: P, children: &BTreeMap<P, Vec<P>>, timer: &mut u32) {
+        self.tin.insert(node, *timer);
+        *timer += 1;
+
+        if let Some(kids) = children.get(&node) {
+            for &child in kids {
+                self.dfs(child, children, timer);
+            }
+        }
+
+        self.tout.insert(node, *timer);
+        *timer += 1;
+    }
+
+    /// True if `point1` dominates `point2`. O(log n), independent of
+    /// tree depth.
+    pub fn dominates(&self, point1: P, point2: P) -> bool {
+        let (tin1, tout1) = self.interval(point1);
+        let (tin2, tout2) = self.interval(point2);
+        tin1 <= tin2 && tout2 <= tout1
+    }
+
+    /// Returns the nearest common ancestor of `point1` and `point2` on
+    /// the dominator tree: walk up from whichever has the larger
+    /// `tin` until its interval contains the other.
+    pub fn mutual_dominator(&self, graph: impl GraphRef<P>, point1: P, point2: P) -> P {
+        let (mut p, other) = if self.interval(point1).0 >= self.interval(point2).0 {
+            (point1, point2)
+        } else {
+            (point2, point1)
+        };
+
+        while !self.dominates(p, other) {
+            p = graph
+                .immediate_dominator(p)
+                .expect("no mutual dominator of the two points");
+        }
+
+        p
+    }
+
+    fn interval(&self, point: P) -> (u32, u32) {
+        let tin = *self.tin.get(&point).expect("point not indexed");
+        let tout = *self.tout.get(&point).expect("point not indexed");
+        (tin, tout)
+    }
+}
+
+/// A `GraphRef` that additionally offers a [`DomTreeIndex`] for
+/// depth-independent dominance queries. Implement this when the full dominator tree can
+/// be materialized up front; callers that only have an on-demand
+/// `GraphRef` still work, just with the slower per-call dominance
+/// walk.
+pub trait PreprocessedGraph<'g, P: Point>: GraphRef<P> {
+    fn dom_tree_index(self) -> &'g DomTreeIndex<P>;
+}
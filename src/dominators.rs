@@ -0,0 +1,203 @@
+use crate::{GraphRef, Point};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A dominator tree built from nothing but a set of successor edges,
+/// using the Cooper-Harvey-Kennedy "A Simple, Fast Dominance
+/// Algorithm". Implements `GraphRef` directly, so a caller who
+/// doesn't want to bring their own dominance analysis (e.g. via
+/// petgraph) can build a `Cfg` and hand it straight to `SemeRegion`.
+#[derive(Clone, Debug)]
+pub struct Cfg<N: Point> {
+    predecessors: BTreeMap<N, Vec<N>>,
+    successors: BTreeMap<N, Vec<N>>,
+    idom: BTreeMap<N, N>,
+    dom_tree_children: BTreeMap<N, Vec<N>>,
+}
+
+impl<N: Point> Cfg<N> {
+    /// Builds a `Cfg` (and its dominator tree) from `(predecessor,
+    /// successor)` edges reachable from `N::entry()`.
+    pub fn new(edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        let mut cfg = Cfg {
+            predecessors: BTreeMap::new(),
+            successors: BTreeMap::new(),
+            idom: BTreeMap::new(),
+            dom_tree_children: BTreeMap::new(),
+        };
+
+        for (pred, succ) in edges {
+            cfg.successors
+                .entry(pred)
+                .or_insert_with(Vec::new)
+                .push(succ);
+            cfg.predecessors
+                .entry(succ)
+                .or_insert_with(Vec::new)
+                .push(pred);
+        }
+
+        cfg.idom = cfg.compute_dominators();
+
+        let entry = N::entry();
+        for (&node, &idom) in &cfg.idom {
+            if node != entry {
+                cfg.dom_tree_children
+                    .entry(idom)
+                    .or_insert_with(Vec::new)
+                    .push(node);
+            }
+        }
+
+        cfg
+    }
+
+    fn successors_of(&self, node: N) -> impl Iterator<Item = N> + '_ {
+        self.successors.get(&node).into_iter().flatten().cloned()
+    }
+
+    /// Computes `idom` via the Cooper-Harvey-Kennedy fixpoint: a
+    /// single reverse-postorder DFS gives each reachable node a
+    /// `post_order_rank`, then we iterate over nodes in reverse
+    /// postorder (excluding the entry) until nothing changes, folding
+    /// multiple predecessors together via `intersect`.
+    fn compute_dominators(&self) -> BTreeMap<N, N> {
+        let entry = N::entry();
+        let mut postorder = Vec::new();
+        let mut visited = BTreeSet::new();
+        self.dfs_postorder(entry, &mut visited, &mut postorder);
+
+        let mut post_order_rank = BTreeMap::new();
+        for (rank, &node) in postorder.iter().enumerate() {
+            post_order_rank.insert(node, rank);
+        }
+
+        // Reverse postorder puts `entry` first.
+        let rpo: Vec<N> = postorder.iter().rev().cloned().collect();
+
+        let mut idom = BTreeMap::new();
+        idom.insert(entry, entry);
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for &node in &rpo[1..] {
+                let mut new_idom = None;
+                for pred in self.predecessors.get(&node).into_iter().flatten().cloned() {
+                    if !idom.contains_key(&pred) {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => pred,
+                        Some(cur) => Self::intersect(cur, pred, &idom, &post_order_rank),
+                    });
+                }
+
+                let new_idom =
+                    new_idom.expect("node is reachable but has no already-processed predecessor");
+                if idom.get(&node) != Some(&new_idom) {
+                    idom.insert(node, new_idom);
+                    changed = true;
+                }
+            }
+        }
+
+        idom
+    }
+
+    fn dfs_postorder(&self, node: N, visited: &mut BTreeSet<N>, postorder: &mut Vec<N>) {
+        if !visited.insert(node) {
+            return;
+        }
+
+        for succ in self.successors_of(node) {
+            self.dfs_postorder(succ, visited, postorder);
+        }
+
+        postorder.push(node);
+    }
+
+    /// Walks `a` and `b` up the (partially built) idom tree,
+    /// repeatedly advancing whichever has the smaller
+    /// `post_order_rank` via its current idom, until they meet.
+    fn intersect(mut a: N, mut b: N, idom: &BTreeMap<N, N>, rank: &BTreeMap<N, usize>) -> N {
+        while a != b {
+            while rank[&a] < rank[&b] {
+                a = idom[&a];
+            }
+            while rank[&b] < rank[&a] {
+                b = idom[&b];
+            }
+        }
+        a
+    }
+}
+
+impl<N: Point> GraphRef<N> for &'g Cfg<N> {
+    type Predecessors = std::vec::IntoIter<N>;
+
+    fn predecessors(self, point: N) -> Self::Predecessors {
+        self.predecessors
+            .get(&point)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+    }
+
+    type Successors = std::vec::IntoIter<N>;
+
+    fn successors(self, point: N) -> Self::Successors {
+        self.successors
+            .get(&point)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+    }
+
+    fn immediate_dominator(self, point: N) -> Option<N> {
+        self.idom.get(&point).copied()
+    }
+
+    fn dominates(self, point1: N, point2: N) -> bool {
+        let mut p = point2;
+        loop {
+            if p == point1 {
+                return true;
+            }
+            if p == N::entry() {
+                return false;
+            }
+            p = self.idom[&p];
+        }
+    }
+
+    fn mutual_dominator(self, point1: N, point2: N) -> N {
+        let mut ancestors = BTreeSet::new();
+        let mut p = point1;
+        loop {
+            ancestors.insert(p);
+            if p == N::entry() {
+                break;
+            }
+            p = self.idom[&p];
+        }
+
+        let mut q = point2;
+        loop {
+            if ancestors.contains(&q) {
+                return q;
+            }
+            q = self.idom[&q];
+        }
+    }
+
+    type DomTreeChildren = std::vec::IntoIter<N>;
+
+    fn dom_tree_children(self, point: N) -> Self::DomTreeChildren {
+        self.dom_tree_children
+            .get(&point)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+    }
+}
@@ -5,8 +5,15 @@
 
 use std::fmt::Debug;
 
+mod dom_tree_index;
+mod dominators;
+mod post_region;
 mod test;
 
+pub use crate::dom_tree_index::{DomTreeIndex, PreprocessedGraph};
+pub use crate::dominators::Cfg;
+pub use crate::post_region::{PostGraphRef, SemePostRegion};
+
 /// Single entry, multiple exit region.
 ///
 /// The region can be visualized as `{H, {Ts}}`, where `H` is the
@@ -40,6 +47,9 @@ pub trait GraphRef<P: Point>: Copy {
     type Predecessors: Iterator<Item = P>;
     fn predecessors(self, point: P) -> Self::Predecessors;
 
+    type Successors: Iterator<Item = P>;
+    fn successors(self, point: P) -> Self::Successors;
+
     /// Returns the immediate dominator of `point` -- if `point` is
     /// the entry to the graph, then returns `point`.
     fn immediate_dominator(self, point: P) -> Option<P>;
@@ -48,6 +58,12 @@ pub trait GraphRef<P: Point>: Copy {
     fn dominates(self, point1: P, point2: P) -> bool;
 
     fn mutual_dominator(self, point1: P, point2: P) -> P;
+
+    type DomTreeChildren: Iterator<Item = P>;
+
+    /// Returns the children of `point` in the dominator tree, i.e.
+    /// every node whose immediate dominator is `point`.
+    fn dom_tree_children(self, point: P) -> Self::DomTreeChildren;
 }
 
 impl<P: Point> SemeRegion<P> {
@@ -73,6 +89,99 @@ impl<P: Point> SemeRegion<P> {
         self.tails.iter().any(|&tail| graph.dominates(point, tail))
     }
 
+    /// Yields every point contained in the region, by walking the
+    /// dominator subtree rooted at `head` and pruning any subtree
+    /// that dominates none of our tails (such a subtree cannot
+    /// contain any in-region points, since dominance is monotonic
+    /// down the tree).
+    pub fn iter_points(&self, graph: impl GraphRef<P>) -> impl Iterator<Item = P> {
+        let mut points = Vec::new();
+
+        if !self.is_empty() {
+            let mut stack = vec![self.head];
+            while let Some(p) = stack.pop() {
+                points.push(p);
+
+                for child in graph.dom_tree_children(p) {
+                    if self.dominates_any_tail(graph, child) {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+
+        points.into_iter()
+    }
+
+    /// Returns the region's outgoing frontier: the `(P, successor)`
+    /// edges where `P` is contained in the region but `successor` is
+    /// not. This is the region's interface to the rest of the graph.
+    pub fn exit_edges(&self, graph: impl GraphRef<P>) -> impl Iterator<Item = (P, P)> {
+        let mut edges = Vec::new();
+
+        for p in self.iter_points(graph) {
+            for successor in graph.successors(p) {
+                if !self.contains(graph, successor) {
+                    edges.push((p, successor));
+                }
+            }
+        }
+
+        edges.into_iter()
+    }
+
+    /// Equivalent to [`SemeRegion::contains`], but uses the
+    /// preprocessed dominator-tree index for O(log n) dominance checks
+    /// instead of the O(depth) walk that plain `GraphRef` does.
+    pub fn contains_fast(&self, graph: impl PreprocessedGraph<'g, P>, point: P) -> bool {
+        let index = graph.dom_tree_index();
+        index.dominates(self.head, point)
+            && self.tails.iter().any(|&tail| index.dominates(point, tail))
+    }
+
+    /// Returns the region containing the points in both `self` and
+    /// `other`. Note that, as with [`SemeRegion::difference`], the
+    /// result may end up containing additional points beyond the
+    /// strict intersection, if they are required to satisfy the
+    /// continuity invariant.
+    pub fn intersect(&self, graph: impl GraphRef<P>, other: &SemeRegion<P>) -> SemeRegion<P> {
+        // Not the most efficient impl, but the easiest and most
+        // readable: walk our own points, keep the ones `other` also
+        // contains, and let `add_point` re-derive a valid head/tails
+        // (restoring continuity) from what's left.
+        let mut result = SemeRegion::empty();
+        for point in self.iter_points(graph) {
+            if other.contains(graph, point) {
+                result.add_point(graph, point);
+            }
+        }
+        result
+    }
+
+    /// Returns the region containing the points in `self` that are
+    /// not in `other`. Note that the result may end up containing
+    /// some points of `other` too, if they are required to satisfy
+    /// the continuity invariant.
+    pub fn difference(&self, graph: impl GraphRef<P>, other: &SemeRegion<P>) -> SemeRegion<P> {
+        let mut result = SemeRegion::empty();
+        for point in self.iter_points(graph) {
+            if !other.contains(graph, point) {
+                result.add_point(graph, point);
+            }
+        }
+        result
+    }
+
+    /// True if every point in `other` is also contained in `self`.
+    pub fn contains_region(&self, graph: impl GraphRef<P>, other: &SemeRegion<P>) -> bool {
+        if other.is_empty() {
+            return true;
+        }
+
+        self.contains(graph, other.head)
+            && other.tails.iter().all(|&tail| self.contains(graph, tail))
+    }
+
     pub fn add_point(&mut self, graph: impl GraphRef<P>, point: P) {
         if self.tails.is_empty() {
             // Region is empty; create singleton region.
@@ -0,0 +1,224 @@
+use crate::Point;
+
+/// Multiple entry, single exit region -- the dual of [`SemeRegion`],
+/// defined over the *post-dominator* tree (the dominator tree of the
+/// reversed CFG, as in the LLVM postdominator construction) instead
+/// of the dominator tree.
+///
+/// The region can be visualized as `{Hs, E}`, where `E` is the "exit"
+/// and `Hs` is a set of "head" nodes. In all cases, E post-dominates
+/// all of the heads (to be valid, there is an add'l "continuity"
+/// requirement, see below). The region contains all points P where:
+///
+/// - E post-dominates P
+/// - there exists some H in Hs where P post-dominates H
+///
+/// In other words, the point P is "in between" some head H and the
+/// exit E on the post-dominator tree. You can test this by walking up
+/// the post-dom tree from each head H until you reach either E or P
+/// -- if you ever find P before E (or if P == E) then P is contained.
+///
+/// To be complete, a region must also be **continuous**:
+///
+/// - For each node N in the region where N != E, all successors of N
+///   are in the region.
+///
+/// [`SemeRegion`]: crate::SemeRegion
+#[derive(Clone, Debug)]
+pub struct SemePostRegion<P: Point> {
+    exit: P,
+    heads: Vec<P>,
+}
+
+pub trait PostGraphRef<P: Point>: Copy {
+    type Successors: Iterator<Item = P>;
+    fn successors(self, point: P) -> Self::Successors;
+
+    /// Returns the immediate post-dominator of `point` -- if `point`
+    /// is the exit of the graph, then returns `point`.
+    fn immediate_post_dominator(self, point: P) -> Option<P>;
+
+    /// True if point1 post-dominates point2.
+    fn post_dominates(self, point1: P, point2: P) -> bool;
+
+    fn mutual_post_dominator(self, point1: P, point2: P) -> P;
+}
+
+impl<P: Point> SemePostRegion<P> {
+    pub fn empty() -> SemePostRegion<P> {
+        SemePostRegion {
+            exit: P::entry(),
+            heads: vec![],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heads.is_empty()
+    }
+
+    /// True if `point` is contained within the region.
+    pub fn contains(&self, graph: impl PostGraphRef<P>, point: P) -> bool {
+        // Not the most efficient impl, but the easiest and most readable.
+        graph.post_dominates(self.exit, point) && self.post_dominates_any_head(graph, point)
+    }
+
+    /// True if `point` post-dominates any of our heads.
+    fn post_dominates_any_head(&self, graph: impl PostGraphRef<P>, point: P) -> bool {
+        self.heads
+            .iter()
+            .any(|&head| graph.post_dominates(point, head))
+    }
+
+    pub fn add_point(&mut self, graph: impl PostGraphRef<P>, point: P) {
+        if self.heads.is_empty() {
+            // Region is empty; create singleton region.
+            self.exit = point;
+            self.heads.push(point);
+            return;
+        }
+
+        if graph.post_dominates(self.exit, point) {
+            return self.add_point_post_dominated_by_exit(graph, point);
+        }
+
+        // The existing exit E does not post-dominate P. We will have
+        // to pick a new exit M that post-dominates both E and P (the
+        // "dashed line" indicates that M and P are not part of the
+        // region yet):
+        //
+        // ```
+        //   H1---Hn
+        //     \   /
+        //      E     P
+        //       :   :
+        //        : :
+        //         M
+        // ```
+
+        let old_exit = self.exit;
+        let new_exit = graph.mutual_post_dominator(old_exit, point);
+        self.exit = new_exit;
+        self.ensure_continuity(graph, new_exit, old_exit);
+
+        // At this point, we have something like this region (note
+        // that ensuring continuity will not modify `self.exit`,
+        // though it may add heads, which we have elided from this
+        // diagram):
+        //
+        //   H1---Hn
+        //     \   /
+        //      E     P
+        //       \   :
+        //        \ :
+        //         M
+        //
+        // Key point is that `P` is now post-dominated by `self.exit`
+        // (which is M), so we can invoke
+        // `add_point_post_dominated_by_exit`.
+
+        self.add_point_post_dominated_by_exit(graph, point);
+    }
+
+    pub fn add_region(&mut self, graph: impl PostGraphRef<P>, region: &SemePostRegion<P>) {
+        if region.is_empty() {
+            return;
+        }
+
+        self.add_point(graph, region.exit);
+        for &head in &region.heads {
+            self.add_point(graph, head)
+        }
+    }
+
+    /// Add `point` to the region in the case where we know that
+    /// `point` is post-dominated by `self.exit`. (See comment in the
+    /// function for detailed breakdown).
+    fn add_point_post_dominated_by_exit(&mut self, graph: impl PostGraphRef<P>, point: P) {
+        debug_assert!(graph.post_dominates(self.exit, point));
+
+        // We now want to distinguish one of a few cases:
+        //
+        // **Noop case:** point post-dominates a head. In that case,
+        // it is already contained in the region.
+        //
+        // **Extension case:** a head post-dominates point. In that
+        // case, we can replace the head with point (and then
+        // "fixup", see below).
+        //
+        // **New case:** point is not related to a known head. Just
+        // have to add a new head.
+        //
+        // In the last two cases, after we adjust the head, we have
+        // to run "fixup". This will walk the new nodes that have
+        // been added and guarantee the continuity invariant.
+        //
+        // To determine which case we are in, we walk up the post-dom
+        // tree from P. If we encounter a head, then we are in the
+        // extension case. If we encounter the exit, then we are
+        // either in the "noop" or "new" case.
+
+        let mut p = point;
+        loop {
+            if let Some(index) = self.heads.iter().position(|&h| h == p) {
+                // Found one of the heads. This is the extension case
+                // -- unless `p == point`, in which case the point is
+                // already contained in the set.
+                if p == point {
+                    return;
+                }
+
+                self.heads[index] = point;
+                return self.ensure_continuity(graph, p, point);
+            }
+
+            if p == self.exit {
+                return self.add_point_post_dominated_by_exit_and_not_by_head(graph, point);
+            }
+
+            p = graph.immediate_post_dominator(p).unwrap();
+        }
+    }
+
+    /// We found that P is post-dominated by the exit but it is *not*
+    /// post-dominated by any of the heads. This means that either P
+    /// is within the region (if it post-dominates a head) or else it
+    /// is a new "branch".
+    fn add_point_post_dominated_by_exit_and_not_by_head(
+        &mut self,
+        graph: impl PostGraphRef<P>,
+        point: P,
+    ) {
+        if self.post_dominates_any_head(graph, point) {
+            // already contained, the "noop" case above.
+            return;
+        }
+
+        // "new" case.
+        self.heads.push(point);
+        let exit = self.exit;
+        self.ensure_continuity(graph, exit, point);
+    }
+
+    /// Ensures that, for any node P that lies between `parent`
+    /// (exclusive) and `child` (inclusive) on the post-dominator
+    /// tree, all successors of P are contained in `self`.
+    ///
+    /// Presuming that `parent` and `child` are both post-dominated by
+    /// `self.exit`, then this routine does not modify `self.exit`.
+    /// The argument is the exact dual of the one for `SemeRegion`:
+    /// any node P strictly post-dominated by `parent` has all of its
+    /// successors Q either equal to `self.exit` or post-dominated by
+    /// `self.exit` (otherwise there would be a path from P that
+    /// bypasses `self.exit`). So adding Q to the region will not
+    /// modify `self.exit`.
+    fn ensure_continuity(&mut self, graph: impl PostGraphRef<P>, parent: P, child: P) {
+        let mut point = child;
+        while point != parent {
+            for successor in graph.successors(point) {
+                self.add_point(graph, successor);
+            }
+
+            point = graph.immediate_post_dominator(point).unwrap();
+        }
+    }
+}
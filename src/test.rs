@@ -1,13 +1,15 @@
 #![cfg(test)]
 
-use crate::{GraphRef, Point, SemeRegion};
+use crate::{Cfg, DomTreeIndex, GraphRef, Point, PostGraphRef, PreprocessedGraph, SemePostRegion, SemeRegion};
 use petgraph::algo::dominators::{self, Dominators};
 use petgraph::graph::{Graph, Neighbors, NodeIndex};
+use petgraph::visit::Reversed;
 use petgraph::Direction;
 
 struct GraphPair {
     graph: Graph<(), ()>,
     dominators: Dominators<NodeIndex>,
+    dom_tree_index: DomTreeIndex<NodeIndex>,
 }
 
 impl GraphPair {
@@ -29,8 +31,55 @@ impl GraphPair {
         }
 
         let dominators = dominators::simple_fast(&graph, NodeIndex::entry());
+        let nodes: Vec<NodeIndex> = graph.node_indices().collect();
 
-        GraphPair { graph, dominators }
+        // `GraphPair` isn't built yet (we still need the index below),
+        // so bootstrap the index off `&dominators` directly.
+        let dom_tree_index = DomTreeIndex::new(DomsOnly(&dominators), nodes);
+
+        GraphPair {
+            graph,
+            dominators,
+            dom_tree_index,
+        }
+    }
+}
+
+/// A minimal `GraphRef` used only to bootstrap a `DomTreeIndex` for a
+/// `GraphPair` that doesn't exist yet. `DomTreeIndex::new` only calls
+/// `immediate_dominator`, so the other methods are never exercised.
+#[derive(Clone, Copy)]
+struct DomsOnly<'g>(&'g Dominators<NodeIndex>);
+
+impl GraphRef<NodeIndex> for DomsOnly<'g> {
+    type Predecessors = std::iter::Empty<NodeIndex>;
+
+    fn predecessors(self, _point: NodeIndex) -> Self::Predecessors {
+        unreachable!("DomsOnly is only used to build a DomTreeIndex")
+    }
+
+    type Successors = std::iter::Empty<NodeIndex>;
+
+    fn successors(self, _point: NodeIndex) -> Self::Successors {
+        unreachable!("DomsOnly is only used to build a DomTreeIndex")
+    }
+
+    fn immediate_dominator(self, point: NodeIndex) -> Option<NodeIndex> {
+        self.0.immediate_dominator(point)
+    }
+
+    fn dominates(self, point1: NodeIndex, point2: NodeIndex) -> bool {
+        self.0.dominators(point2).unwrap().any(|p| p == point1)
+    }
+
+    fn mutual_dominator(self, _point1: NodeIndex, _point2: NodeIndex) -> NodeIndex {
+        unreachable!("DomsOnly is only used to build a DomTreeIndex")
+    }
+
+    type DomTreeChildren = std::iter::Empty<NodeIndex>;
+
+    fn dom_tree_children(self, _point: NodeIndex) -> Self::DomTreeChildren {
+        unreachable!("DomsOnly is only used to build a DomTreeIndex")
     }
 }
 
@@ -47,6 +96,12 @@ impl GraphRef<NodeIndex> for &'g GraphPair {
         self.graph.neighbors_directed(point, Direction::Incoming)
     }
 
+    type Successors = Neighbors<'g, ()>;
+
+    fn successors(self, point: NodeIndex) -> Self::Successors {
+        self.graph.neighbors_directed(point, Direction::Outgoing)
+    }
+
     fn immediate_dominator(self, point: NodeIndex) -> Option<NodeIndex> {
         self.dominators.immediate_dominator(point)
     }
@@ -67,6 +122,86 @@ impl GraphRef<NodeIndex> for &'g GraphPair {
         }
         panic!("no mutual dominator of {:?} and {:?}", point1, point2)
     }
+
+    type DomTreeChildren = std::vec::IntoIter<NodeIndex>;
+
+    fn dom_tree_children(self, point: NodeIndex) -> Self::DomTreeChildren {
+        self.graph
+            .node_indices()
+            .filter(|&n| n != point && self.dominators.immediate_dominator(n) == Some(point))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<'g> PreprocessedGraph<'g, NodeIndex> for &'g GraphPair {
+    fn dom_tree_index(self) -> &'g DomTreeIndex<NodeIndex> {
+        &self.dom_tree_index
+    }
+}
+
+/// Like `GraphPair`, but wraps the *post*-dominator tree (the
+/// dominator tree of the reversed graph, rooted at `exit`) for
+/// testing `SemePostRegion`.
+struct PostGraphPair {
+    graph: Graph<(), ()>,
+    post_dominators: Dominators<NodeIndex>,
+}
+
+impl PostGraphPair {
+    fn new(edges: &[(usize, usize)], exit: usize) -> PostGraphPair {
+        let num_nodes = edges
+            .iter()
+            .map(|(a, b)| ::std::cmp::max(a + 1, b + 1))
+            .max()
+            .unwrap_or(0);
+
+        let mut graph = Graph::new();
+
+        for _ in 0..num_nodes {
+            graph.add_node(());
+        }
+
+        for &(p, q) in edges {
+            graph.add_edge(NodeIndex::new(p), NodeIndex::new(q), ());
+        }
+
+        let post_dominators = dominators::simple_fast(Reversed(&graph), NodeIndex::new(exit));
+
+        PostGraphPair {
+            graph,
+            post_dominators,
+        }
+    }
+}
+
+impl PostGraphRef<NodeIndex> for &'g PostGraphPair {
+    type Successors = Neighbors<'g, ()>;
+
+    fn successors(self, point: NodeIndex) -> Self::Successors {
+        self.graph.neighbors_directed(point, Direction::Outgoing)
+    }
+
+    fn immediate_post_dominator(self, point: NodeIndex) -> Option<NodeIndex> {
+        self.post_dominators.immediate_dominator(point)
+    }
+
+    fn post_dominates(self, point1: NodeIndex, point2: NodeIndex) -> bool {
+        self.post_dominators
+            .dominators(point2)
+            .unwrap()
+            .any(|p| p == point1)
+    }
+
+    fn mutual_post_dominator(self, point1: NodeIndex, point2: NodeIndex) -> NodeIndex {
+        for p in self.post_dominators.dominators(point2).unwrap() {
+            // invariant: p post-dominates point2
+            if self.post_dominates(p, point1) {
+                return p;
+            }
+        }
+        panic!("no mutual post dominator of {:?} and {:?}", point1, point2)
+    }
 }
 
 macro_rules! assert_contents {
@@ -141,3 +276,206 @@ fn diamond2() {
     r.add_point(g, NodeIndex::new(3));
     assert_contents!(r, g, +[0, 1, 2, 3] -[]);
 }
+
+#[test]
+fn contains_fast_agrees_with_contains() {
+    // Flow -->
+    //
+    //     1
+    //   /   \
+    // 0      3
+    //   \   /
+    //     2
+
+    let g = &GraphPair::new(&[(0, 1), (0, 2), (1, 3), (2, 3)]);
+    let mut r = SemeRegion::empty();
+
+    r.add_point(g, NodeIndex::new(3));
+    r.add_point(g, NodeIndex::new(1));
+
+    for i in 0..4 {
+        let p = NodeIndex::new(i);
+        assert_eq!(r.contains(g, p), r.contains_fast(g, p));
+    }
+}
+
+#[test]
+fn dom_tree_index_mutual_dominator_agrees_with_graph_ref() {
+    // Flow -->
+    //
+    //     1
+    //   /   \
+    // 0      3
+    //   \   /
+    //     2
+
+    let g = &GraphPair::new(&[(0, 1), (0, 2), (1, 3), (2, 3)]);
+    let index = g.dom_tree_index();
+
+    for i in 0..4 {
+        for j in 0..4 {
+            let (p, q) = (NodeIndex::new(i), NodeIndex::new(j));
+            assert_eq!(index.mutual_dominator(g, p, q), g.mutual_dominator(p, q));
+        }
+    }
+}
+
+#[test]
+fn cfg_computes_same_dominators_as_petgraph() {
+    // Flow -->
+    //
+    //     1
+    //   /   \
+    // 0      3
+    //   \   /
+    //     2
+
+    let edges = [(0usize, 1usize), (0, 2), (1, 3), (2, 3)];
+    let cfg = Cfg::new(
+        edges
+            .iter()
+            .map(|&(p, q)| (NodeIndex::new(p), NodeIndex::new(q))),
+    );
+    let cfg = &cfg;
+    let g = &GraphPair::new(&edges);
+
+    for i in 0..4 {
+        let p = NodeIndex::new(i);
+        // petgraph reports no immediate dominator for its root, whereas
+        // `GraphRef::immediate_dominator` returns the entry itself.
+        let expected = if p == NodeIndex::entry() {
+            Some(p)
+        } else {
+            g.immediate_dominator(p)
+        };
+        assert_eq!(cfg.immediate_dominator(p), expected);
+    }
+
+    let mut r = SemeRegion::empty();
+    r.add_point(cfg, NodeIndex::new(3));
+    r.add_point(cfg, NodeIndex::new(1));
+    assert_contents!(r, cfg, +[0, 1, 2, 3] -[]);
+}
+
+#[test]
+fn post_diamond1() {
+    // Flow -->
+    //
+    //     1
+    //   /   \
+    // 0      3
+    //   \   /
+    //     2
+
+    let g = &PostGraphPair::new(&[(0, 1), (0, 2), (1, 3), (2, 3)], 3);
+    let mut r = SemePostRegion::empty();
+
+    r.add_point(g, NodeIndex::new(0));
+    assert_contents!(r, g, +[0] -[1, 2, 3]);
+
+    // Adding 2 forces us to contain 3, because that is the mutual
+    // post-dominator of 0 and 2. Once 0 is no longer the exit, we
+    // must have 1 (its other successor).
+    r.add_point(g, NodeIndex::new(2));
+    assert_contents!(r, g, +[0, 1, 2, 3] -[]);
+}
+
+#[test]
+fn post_diamond2() {
+    // Flow -->
+    //
+    //     1
+    //   /   \
+    // 0      3
+    //   \   /
+    //     2
+
+    let g = &PostGraphPair::new(&[(0, 1), (0, 2), (1, 3), (2, 3)], 3);
+
+    // We can contain 3 and 1
+    let mut r = SemePostRegion::empty();
+    r.add_point(g, NodeIndex::new(3));
+    r.add_point(g, NodeIndex::new(1));
+    assert_contents!(r, g, +[3, 1] -[0, 2]);
+
+    // We can contain 3 and 2
+    let mut r = SemePostRegion::empty();
+    r.add_point(g, NodeIndex::new(3));
+    r.add_point(g, NodeIndex::new(2));
+    assert_contents!(r, g, +[3, 2] -[0, 1]);
+
+    // But 3 and 0 forces 1 and 2
+    let mut r = SemePostRegion::empty();
+    r.add_point(g, NodeIndex::new(3));
+    r.add_point(g, NodeIndex::new(0));
+    assert_contents!(r, g, +[0, 1, 2, 3] -[]);
+}
+
+#[test]
+fn iter_points_and_exit_edges() {
+    // Flow -->
+    //
+    //         1
+    //       /   \
+    //     0      3 --- 4
+    //       \   /
+    //         2
+
+    let g = &GraphPair::new(&[(0, 1), (0, 2), (1, 3), (2, 3), (3, 4)]);
+
+    let mut r = SemeRegion::empty();
+    r.add_point(g, NodeIndex::new(1));
+    r.add_point(g, NodeIndex::new(2));
+
+    // {0, 1, 2} is the region, with the edges out to 3 as its
+    // frontier (4 is not even adjacent to the region).
+    let mut points: Vec<_> = r.iter_points(g).map(|p| p.index()).collect();
+    points.sort();
+    assert_eq!(points, vec![0, 1, 2]);
+
+    let mut exits: Vec<_> = r
+        .exit_edges(g)
+        .map(|(from, to)| (from.index(), to.index()))
+        .collect();
+    exits.sort();
+    assert_eq!(exits, vec![(1, 3), (2, 3)]);
+}
+
+#[test]
+fn region_algebra() {
+    // Flow -->
+    //
+    //     1
+    //   /   \
+    // 0      3
+    //   \   /
+    //     2
+
+    let g = &GraphPair::new(&[(0, 1), (0, 2), (1, 3), (2, 3)]);
+
+    let mut a = SemeRegion::empty();
+    a.add_point(g, NodeIndex::new(0));
+    a.add_point(g, NodeIndex::new(1));
+    assert_contents!(a, g, +[0, 1] -[2, 3]);
+
+    let mut b = SemeRegion::empty();
+    b.add_point(g, NodeIndex::new(0));
+    b.add_point(g, NodeIndex::new(2));
+    assert_contents!(b, g, +[0, 2] -[1, 3]);
+
+    let intersection = a.intersect(g, &b);
+    assert_contents!(intersection, g, +[0] -[1, 2, 3]);
+
+    let diff = a.difference(g, &b);
+    assert_contents!(diff, g, +[1] -[0, 2, 3]);
+
+    let mut full = SemeRegion::empty();
+    full.add_point(g, NodeIndex::new(0));
+    full.add_point(g, NodeIndex::new(3));
+    assert_contents!(full, g, +[0, 1, 2, 3] -[]);
+
+    assert!(full.contains_region(g, &a));
+    assert!(full.contains_region(g, &b));
+    assert!(!a.contains_region(g, &full));
+    assert!(!a.contains_region(g, &b));
+}